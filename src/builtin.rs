@@ -0,0 +1,49 @@
+//! Builtin relations: properties evaluated by Rust closures instead of being matched against
+//! stored facts. This is how a rule expresses a constraint, such as `(?a <lessThan> ?b)`, that
+//! cannot be enumerated as ground triples — analogous to a trait solver special-casing a
+//! handful of built-in traits rather than resolving them from an impl list.
+//!
+//! A [`BuiltinRegistry`] is only consulted by the backward-chaining solver (`crate::solve::solve`)
+//! today. `Rule::lower`, which feeds the forward-chaining reasoner, does not consult it — see the
+//! note on [`crate::rule::Rule::lower`].
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// A single builtin relation.
+pub enum Builtin<Bound> {
+    /// Prunes candidate instantiations. Both the subject and object must already be bound by an
+    /// earlier `if_all` triple before this relation is evaluated.
+    Filter(Box<dyn Fn(&Bound, &Bound) -> bool>),
+    /// Produces object bindings for an already-bound subject. Only the subject must already be
+    /// bound; the object(s) this produces become newly bound.
+    Generator(Box<dyn Fn(&Bound) -> Vec<Bound>>),
+}
+
+/// Maps property values to the builtin relation they invoke. Registered under the `Bound` value
+/// that names the relation in rule source, e.g. registering under `<lessThan>` lets rules write
+/// `(?a <lessThan> ?b)` even though no triple with that property is ever asserted as fact.
+pub struct BuiltinRegistry<Bound: Ord>(BTreeMap<Bound, Builtin<Bound>>);
+
+impl<Bound: Ord> BuiltinRegistry<Bound> {
+    pub fn new() -> Self {
+        Self(BTreeMap::new())
+    }
+
+    /// Register a builtin under the property value that invokes it. Replaces any builtin
+    /// previously registered under the same property.
+    pub fn register(&mut self, property: Bound, builtin: Builtin<Bound>) {
+        self.0.insert(property, builtin);
+    }
+
+    pub(crate) fn get(&self, property: &Bound) -> Option<&Builtin<Bound>> {
+        self.0.get(property)
+    }
+}
+
+impl<Bound: Ord> Default for BuiltinRegistry<Bound> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
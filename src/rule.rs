@@ -5,6 +5,7 @@
 use crate::common::inc;
 use crate::reasoner::{self, Triple};
 use crate::translator::Translator;
+use crate::unify::Subst;
 use alloc::collections::BTreeMap;
 use alloc::collections::BTreeSet;
 use core::fmt::Debug;
@@ -68,6 +69,12 @@ impl<'a, Unbound: Ord + Clone, Bound: Ord> Rule<Unbound, Bound> {
         Ok(Self { if_all, then })
     }
 
+    /// Lower a rule for the forward-chaining reasoner. This does not special-case builtin
+    /// properties (see [`crate::builtin`]): a triple naming one is lowered like any other, and
+    /// requires a translator entry the same as a stored fact would. Builtins are evaluated only
+    /// on the backward-chaining path (`crate::solve::solve`) today; extending that to
+    /// forward-chaining would mean teaching the reasoner's own match loop about
+    /// `BuiltinRegistry`, which is out of scope here.
     pub(crate) fn lower(&self, tran: &Translator<Bound>) -> Result<LowRule, NoTranslation<&Bound>> {
         // There are three types of name at play here.
         // - human names are represented as Entities
@@ -171,10 +178,87 @@ impl<'a, Unbound: Ord, Bound> Rule<Unbound, Bound> {
     }
 }
 
+impl<Unbound: Ord, Bound: Ord + Clone> Rule<Unbound, Bound> {
+    /// Rename every unbound name to a `u32`, assigned in first-occurrence order over `if_all`
+    /// then `then`. Two rules that are identical up to the spelling of their unbound names
+    /// produce byte-identical output, so the result doubles as a dedup key: collect a rule set
+    /// into a `BTreeSet<Rule<u32, Bound>>` to drop alpha-equivalent duplicates before lowering.
+    pub fn canonicalize(&self) -> Rule<u32, Bound> {
+        // `then`'s unbound names are a subset of `if_all`'s (the invariant `create` enforces), and
+        // `cononical_unbound` already walks `if_all` in first-occurrence order, so assigning names
+        // from it up front guarantees every name `then` needs is already assigned.
+        let mut next_local = 0u32;
+        let mut renamed = BTreeMap::<&Unbound, u32>::new();
+        for unbound in self.cononical_unbound() {
+            renamed.entry(unbound).or_insert_with(|| inc(&mut next_local));
+        }
+
+        let rename_entity = |entity: &Entity<Unbound, Bound>| -> Entity<u32, Bound> {
+            match entity {
+                Entity::Any(unbound) => Entity::Any(renamed[unbound]),
+                Entity::Exactly(bound) => Entity::Exactly(bound.clone()),
+            }
+        };
+        let rename_triple = |[s, p, o]: &[Entity<Unbound, Bound>; 3]| -> [Entity<u32, Bound>; 3] {
+            [rename_entity(s), rename_entity(p), rename_entity(o)]
+        };
+
+        let if_all = self.if_all.iter().map(rename_triple).collect();
+        let then = self.then.iter().map(rename_triple).collect();
+
+        Rule { if_all, then }
+    }
+}
+
 impl<'a, Unbound, Bound> Rule<Unbound, Bound> {
     pub fn iter_entities(&self) -> impl Iterator<Item = &Entity<Unbound, Bound>> {
         self.if_all.iter().chain(self.then.iter()).flatten()
     }
+
+    /// The rule's premises, structured as triples rather than flattened. Crate-internal: outside
+    /// this module a `Rule`'s fields stay private so its invariant can't be broken by a caller.
+    pub(crate) fn if_all(&self) -> &[[Entity<Unbound, Bound>; 3]] {
+        &self.if_all
+    }
+
+    /// The rule's conclusions, structured as triples rather than flattened. Crate-internal for
+    /// the same reason as [`Rule::if_all`].
+    pub(crate) fn then(&self) -> &[[Entity<Unbound, Bound>; 3]] {
+        &self.then
+    }
+}
+
+impl<Unbound: Ord + Clone, Bound: Ord + Clone> Subst<Unbound, Bound> {
+    /// Materialize a solved substitution back into a rule by resolving every entity through it.
+    /// Goes through `Rule::create` to re-check the "unbound-in-then ⊆ unbound-in-if" invariant:
+    /// unifying against a rule's `if_all` side can merge its variables with ones that never
+    /// appear in `then`, so the invariant is re-checked here rather than assumed to still hold.
+    pub fn apply(&mut self, rule: &Rule<Unbound, Bound>) -> Result<Rule<Unbound, Bound>, InvalidRule<Unbound>> {
+        fn resolve_entity<Unbound: Ord + Clone, Bound: Ord + Clone>(
+            env: &mut Subst<Unbound, Bound>,
+            entity: &Entity<Unbound, Bound>,
+        ) -> Entity<Unbound, Bound> {
+            match entity {
+                Entity::Any(name) => env.resolve(name),
+                Entity::Exactly(value) => Entity::Exactly(value.clone()),
+            }
+        }
+        fn resolve_triple<Unbound: Ord + Clone, Bound: Ord + Clone>(
+            env: &mut Subst<Unbound, Bound>,
+            [s, p, o]: &[Entity<Unbound, Bound>; 3],
+        ) -> [Entity<Unbound, Bound>; 3] {
+            [
+                resolve_entity(env, s),
+                resolve_entity(env, p),
+                resolve_entity(env, o),
+            ]
+        }
+
+        let if_all = rule.if_all.iter().map(|t| resolve_triple(self, t)).collect();
+        let then = rule.then.iter().map(|t| resolve_triple(self, t)).collect();
+
+        Rule::create(if_all, then)
+    }
 }
 
 #[derive(Debug)]
@@ -374,4 +458,92 @@ mod test {
     fn create_invalid() {
         Rule::<&str, &str>::create(vec![], vec![[any("a"), any("a"), any("a")]]).unwrap_err();
     }
+
+    #[test]
+    fn canonicalize_alpha_equivalent() {
+        // (?a parent ?b) -> (?a ancestor ?b)
+        let rule_a = Rule::<&str, &str>::create(
+            vec![[any("a"), exa("parent"), any("b")]],
+            vec![[any("a"), exa("ancestor"), any("b")]],
+        )
+        .unwrap();
+        // (?x parent ?y) -> (?x ancestor ?y)
+        let rule_b = Rule::<&str, &str>::create(
+            vec![[any("x"), exa("parent"), any("y")]],
+            vec![[any("x"), exa("ancestor"), any("y")]],
+        )
+        .unwrap();
+
+        assert_eq!(rule_a.canonicalize(), rule_b.canonicalize());
+    }
+
+    #[test]
+    fn canonicalize_distinguishes_different_rules() {
+        // (?a parent ?b) -> (?a ancestor ?b)
+        let rule_a = Rule::<&str, &str>::create(
+            vec![[any("a"), exa("parent"), any("b")]],
+            vec![[any("a"), exa("ancestor"), any("b")]],
+        )
+        .unwrap();
+        // (?a parent ?b) -> (?b ancestor ?a)   (arguments swapped)
+        let rule_b = Rule::<&str, &str>::create(
+            vec![[any("a"), exa("parent"), any("b")]],
+            vec![[any("b"), exa("ancestor"), any("a")]],
+        )
+        .unwrap();
+
+        assert_ne!(rule_a.canonicalize(), rule_b.canonicalize());
+    }
+
+    #[test]
+    fn canonicalize_dedups_in_btreeset() {
+        use alloc::collections::BTreeSet;
+
+        let rule_a = Rule::<&str, &str>::create(
+            vec![[any("a"), exa("parent"), any("b")]],
+            vec![[any("a"), exa("ancestor"), any("b")]],
+        )
+        .unwrap();
+        let rule_b = Rule::<&str, &str>::create(
+            vec![[any("x"), exa("parent"), any("y")]],
+            vec![[any("x"), exa("ancestor"), any("y")]],
+        )
+        .unwrap();
+
+        let deduped: BTreeSet<_> = vec![rule_a.canonicalize(), rule_b.canonicalize()]
+            .into_iter()
+            .collect();
+        assert_eq!(deduped.len(), 1);
+    }
+
+    #[test]
+    fn subst_apply_resolves_then() {
+        use crate::unify::unify;
+
+        // (?a parent ?b) -> (?a ancestor ?b)
+        let rule = Rule::<&str, &str>::create(
+            vec![[any("a"), exa("parent"), any("b")]],
+            vec![[any("a"), exa("ancestor"), any("b")]],
+        )
+        .unwrap();
+
+        // solve ?a against <alice>
+        let mut env = Subst::<&str, &str>::new();
+        unify(
+            &[any("a"), exa("parent"), any("b")],
+            &[exa("alice"), exa("parent"), any("b")],
+            &mut env,
+        )
+        .unwrap();
+
+        let solved = env.apply(&rule).unwrap();
+        assert_eq!(
+            solved,
+            Rule::create(
+                vec![[exa("alice"), exa("parent"), any("b")]],
+                vec![[exa("alice"), exa("ancestor"), any("b")]],
+            )
+            .unwrap()
+        );
+    }
 }
@@ -0,0 +1,370 @@
+//! Backward-chaining (SLD-resolution style) goal solving, alongside the forward lowering in
+//! `rule.rs`. Given a goal triple pattern, a fact set, and a rule set, [`solve`] proves the goal
+//! by working backwards: unify it against a fact, evaluate it against a registered builtin
+//! relation, or unify it against a rule's conclusion (after freshening the rule's unbound names
+//! so they can't collide with the goal's) and recurse into that rule's premises as new subgoals.
+//! Each successful derivation yields a [`Solution`]: the substitution that proves the goal, and
+//! the [`Proof`] tree justifying it.
+//!
+//! Facts and rules are expected in canonical form (see `Rule::canonicalize`): unbound names are
+//! `u32`s assigned in a contiguous range starting at `0`, which is what lets freshening just add
+//! an offset rather than building a fresh rename table per rule application.
+
+use crate::builtin::{Builtin, BuiltinRegistry};
+use crate::rule::{Entity, Rule};
+use crate::unify::{unify, unify_one, Subst};
+use alloc::vec::Vec;
+
+/// The tree of rule applications that justifies a goal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Proof<Bound> {
+    /// The goal unified directly against a stored fact.
+    Fact([Bound; 3]),
+    /// The goal was discharged by calling a registered builtin relation, resolved as far as
+    /// possible.
+    Builtin([Entity<u32, Bound>; 3]),
+    /// The goal was discharged by applying a rule (after freshening), whose premises are
+    /// justified in turn.
+    Rule {
+        then: [Entity<u32, Bound>; 3],
+        premises: Vec<Proof<Bound>>,
+    },
+}
+
+/// One way to prove the goal: the substitution that makes it hold, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution<Bound> {
+    pub subst: Subst<u32, Bound>,
+    pub proof: Proof<Bound>,
+}
+
+/// How deep `solve` is willing to recurse before giving up on a branch. Without a bound, a
+/// cyclic rule set (e.g. transitive `ancestor`) would recurse forever.
+const DEFAULT_DEPTH_LIMIT: usize = 64;
+
+/// Prove `goal` against `facts` and `rules`, yielding one [`Solution`] per successful derivation.
+/// `rules` must already be canonicalized (`Rule::canonicalize`). `goal`'s unbound names may be
+/// any `u32`s: every rule application is renamed past the goal's highest unbound name, so rule
+/// names never collide with the goal's.
+pub fn solve<Bound: Ord + Clone>(
+    goal: [Entity<u32, Bound>; 3],
+    facts: &[[Bound; 3]],
+    rules: &[Rule<u32, Bound>],
+    builtins: &BuiltinRegistry<Bound>,
+) -> alloc::vec::IntoIter<Solution<Bound>> {
+    let next_fresh = goal
+        .iter()
+        .filter_map(Entity::as_unbound)
+        .cloned()
+        .max()
+        .map_or(0, |highest| highest + 1);
+    let mut solver = Solver {
+        facts,
+        rules,
+        builtins,
+        depth_limit: DEFAULT_DEPTH_LIMIT,
+        next_fresh,
+    };
+    let mut solutions = Vec::new();
+    for (subst, proof) in solver.prove_one(&goal, Subst::new(), 0) {
+        solutions.push(Solution { subst, proof });
+    }
+    solutions.into_iter()
+}
+
+struct Solver<'r, Bound> {
+    facts: &'r [[Bound; 3]],
+    rules: &'r [Rule<u32, Bound>],
+    builtins: &'r BuiltinRegistry<Bound>,
+    depth_limit: usize,
+    next_fresh: u32,
+}
+
+impl<'r, Bound: Ord + Clone> Solver<'r, Bound> {
+    /// Rename every unbound name in `rule` by adding `self.next_fresh` to it, then advance the
+    /// counter past every name just assigned. Relies on `rule` being canonical (unbound names
+    /// contiguous from `0`), so a single offset is enough to make every name in this
+    /// instantiation disjoint from every name used so far in the derivation.
+    fn freshen(&mut self, rule: &Rule<u32, Bound>) -> Rule<u32, Bound> {
+        let offset = self.next_fresh;
+        let width = rule.cononical_unbound().count() as u32;
+        self.next_fresh += width;
+
+        let shift = |e: &Entity<u32, Bound>| -> Entity<u32, Bound> {
+            match e {
+                Entity::Any(name) => Entity::Any(name + offset),
+                Entity::Exactly(value) => Entity::Exactly(value.clone()),
+            }
+        };
+        let shift_triple = |[s, p, o]: &[Entity<u32, Bound>; 3]| [shift(s), shift(p), shift(o)];
+
+        Rule::create(
+            rule.if_all().iter().map(shift_triple).collect(),
+            rule.then().iter().map(shift_triple).collect(),
+        )
+        .expect("shifting every unbound name by the same offset preserves the rule's invariant")
+    }
+
+    /// Prove every goal in `goals` in order under `env`, threading the substitution from one
+    /// goal to the next, and backtracking over every combination of choices that succeeds.
+    fn prove_all(
+        &mut self,
+        goals: &[[Entity<u32, Bound>; 3]],
+        env: Subst<u32, Bound>,
+        depth: usize,
+    ) -> Vec<(Subst<u32, Bound>, Vec<Proof<Bound>>)> {
+        match goals.split_first() {
+            None => alloc::vec![(env, Vec::new())],
+            Some((first, rest)) => self
+                .prove_one(first, env, depth)
+                .into_iter()
+                .flat_map(|(env, proof)| {
+                    self.prove_all(rest, env, depth)
+                        .into_iter()
+                        .map(move |(env, mut premises)| {
+                            premises.insert(0, proof.clone());
+                            (env, premises)
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect(),
+        }
+    }
+
+    /// Prove a single goal under `env`, backtracking over every fact and rule that can discharge
+    /// it.
+    fn prove_one(
+        &mut self,
+        goal: &[Entity<u32, Bound>; 3],
+        env: Subst<u32, Bound>,
+        depth: usize,
+    ) -> Vec<(Subst<u32, Bound>, Proof<Bound>)> {
+        let mut out = Vec::new();
+
+        for fact in self.facts {
+            let mut candidate = env.clone();
+            let as_entities = [
+                Entity::Exactly(fact[0].clone()),
+                Entity::Exactly(fact[1].clone()),
+                Entity::Exactly(fact[2].clone()),
+            ];
+            if unify(goal, &as_entities, &mut candidate).is_some() {
+                out.push((candidate, Proof::Fact(fact.clone())));
+            }
+        }
+
+        out.extend(self.prove_builtin(goal, &env));
+
+        // Only rule expansion recurses, so only it needs to respect the depth bound - a goal
+        // that's trivially provable by a fact or builtin above must still succeed even when
+        // reached right at the limit.
+        if depth < self.depth_limit {
+            // Freshen every rule up front so a rule used twice in one derivation (e.g. transitive
+            // `ancestor`) gets a distinct set of names each time, keeping the two applications
+            // from being unsoundly conflated. `self.rules` is borrowed from `'r`, not from
+            // `&self`, so freshening each in place needs no clone of the rule set itself.
+            for rule in self.rules {
+                let fresh_rule = self.freshen(rule);
+                for head in fresh_rule.then() {
+                    let mut candidate = env.clone();
+                    if unify(goal, head, &mut candidate).is_some() {
+                        for (mut final_env, premises) in
+                            self.prove_all(fresh_rule.if_all(), candidate, depth + 1)
+                        {
+                            let [s, p, o] = head;
+                            let then = [
+                                resolve(&mut final_env, s),
+                                resolve(&mut final_env, p),
+                                resolve(&mut final_env, o),
+                            ];
+                            out.push((final_env, Proof::Rule { then, premises }));
+                        }
+                    }
+                }
+            }
+        }
+
+        out
+    }
+
+    /// Discharge `goal` against whatever builtin relation its property names, if any. A `Filter`
+    /// only fires once both the subject and object are already bound; a `Generator` only needs
+    /// the subject bound, and contributes one candidate solution per produced value that unifies
+    /// with the object.
+    fn prove_builtin(
+        &mut self,
+        goal: &[Entity<u32, Bound>; 3],
+        env: &Subst<u32, Bound>,
+    ) -> Vec<(Subst<u32, Bound>, Proof<Bound>)> {
+        let [s, p, o] = goal;
+        let Entity::Exactly(property) = p else {
+            return Vec::new();
+        };
+        let Some(builtin) = self.builtins.get(property) else {
+            return Vec::new();
+        };
+
+        let mut probe = env.clone();
+        let subject = resolve(&mut probe, s);
+        let Entity::Exactly(subject) = subject else {
+            return Vec::new();
+        };
+
+        match builtin {
+            Builtin::Filter(filter) => {
+                let object = resolve(&mut probe, o);
+                let Entity::Exactly(object) = object else {
+                    return Vec::new();
+                };
+                if filter(&subject, &object) {
+                    let resolved = [Entity::Exactly(subject), p.clone(), Entity::Exactly(object)];
+                    alloc::vec![(env.clone(), Proof::Builtin(resolved))]
+                } else {
+                    Vec::new()
+                }
+            }
+            Builtin::Generator(generator) => generator(&subject)
+                .into_iter()
+                .filter_map(|value| {
+                    let mut candidate = env.clone();
+                    unify_one(o, &Entity::Exactly(value), &mut candidate)?;
+                    let object = resolve(&mut candidate, o);
+                    let resolved = [Entity::Exactly(subject.clone()), p.clone(), object];
+                    Some((candidate, Proof::Builtin(resolved)))
+                })
+                .collect(),
+        }
+    }
+}
+
+fn resolve<Bound: Ord + Clone>(
+    env: &mut Subst<u32, Bound>,
+    entity: &Entity<u32, Bound>,
+) -> Entity<u32, Bound> {
+    match entity {
+        Entity::Any(name) => env.resolve(name),
+        Entity::Exactly(value) => Entity::Exactly(value.clone()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::{any, exa};
+    use alloc::boxed::Box;
+
+    #[test]
+    fn solve_direct_fact() {
+        let facts = [["alice", "parent", "bob"]];
+        let goal = [any(0), exa("parent"), exa("bob")];
+
+        let solutions: Vec<_> = solve(goal, &facts, &[], &BuiltinRegistry::new()).collect();
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].proof, Proof::Fact(["alice", "parent", "bob"]));
+    }
+
+    #[test]
+    fn solve_one_hop_rule() {
+        // (?a parent ?b) -> (?a ancestor ?b)
+        let rule = Rule::<u32, &str>::create(
+            vec![[any(0), exa("parent"), any(1)]],
+            vec![[any(0), exa("ancestor"), any(1)]],
+        )
+        .unwrap();
+        let facts = [["alice", "parent", "bob"]];
+        let goal = [exa("alice"), exa("ancestor"), any(0)];
+
+        let solutions: Vec<_> = solve(goal, &facts, &[rule], &BuiltinRegistry::new()).collect();
+        assert_eq!(solutions.len(), 1);
+    }
+
+    #[test]
+    fn solve_transitive_rule_two_hops() {
+        // (?a parent ?b) -> (?a ancestor ?b)
+        // (?a ancestor ?b) and (?b ancestor ?c) -> (?a ancestor ?c)
+        let base = Rule::<u32, &str>::create(
+            vec![[any(0), exa("parent"), any(1)]],
+            vec![[any(0), exa("ancestor"), any(1)]],
+        )
+        .unwrap();
+        let transitive = Rule::<u32, &str>::create(
+            vec![
+                [any(0), exa("ancestor"), any(1)],
+                [any(1), exa("ancestor"), any(2)],
+            ],
+            vec![[any(0), exa("ancestor"), any(2)]],
+        )
+        .unwrap();
+        let facts = [["alice", "parent", "bob"], ["bob", "parent", "carol"]];
+        let goal = [exa("alice"), exa("ancestor"), exa("carol")];
+
+        let solutions: Vec<_> =
+            solve(goal, &facts, &[base, transitive], &BuiltinRegistry::new()).collect();
+        assert!(!solutions.is_empty());
+    }
+
+    #[test]
+    fn solve_no_match_yields_no_solutions() {
+        let facts = [["alice", "parent", "bob"]];
+        let goal = [exa("alice"), exa("parent"), exa("carol")];
+
+        let solutions: Vec<_> = solve(goal, &facts, &[], &BuiltinRegistry::new()).collect();
+        assert!(solutions.is_empty());
+    }
+
+    #[test]
+    fn solve_self_referential_rule_terminates_via_depth_limit() {
+        // (?a r ?b) -> (?a r ?b): a rule that restates its own premise as its conclusion, so
+        // without a depth bound `solve` would recurse into it forever trying to ground `?a r ?b`.
+        // There are no facts, so no derivation can ever succeed, but the call must still return
+        // (rather than overflow the stack or hang) once the depth limit kicks in.
+        let rule = Rule::<u32, &str>::create(
+            vec![[any(0), exa("r"), any(1)]],
+            vec![[any(0), exa("r"), any(1)]],
+        )
+        .unwrap();
+        let goal = [any(0), exa("r"), any(1)];
+
+        let solutions: Vec<_> = solve(goal, &[], &[rule], &BuiltinRegistry::new()).collect();
+        assert!(solutions.is_empty());
+    }
+
+    #[test]
+    fn solve_builtin_filter() {
+        // `differentFrom` is a builtin filter over an already-bound subject and object; the goal
+        // invokes it directly, so unlike a plain fact lookup this only succeeds if the closure is
+        // actually called and returns true.
+        let mut builtins = BuiltinRegistry::new();
+        builtins.register(
+            "differentFrom",
+            Builtin::Filter(Box::new(|a: &&str, b: &&str| a != b)),
+        );
+
+        let passing = [exa("alice"), exa("differentFrom"), exa("bob")];
+        let solutions: Vec<_> = solve(passing, &[], &[], &builtins).collect();
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].proof, Proof::Builtin(passing));
+
+        let failing = [exa("alice"), exa("differentFrom"), exa("alice")];
+        let solutions: Vec<_> = solve(failing, &[], &[], &builtins).collect();
+        assert!(solutions.is_empty());
+    }
+
+    #[test]
+    fn solve_builtin_generator() {
+        // `siblingOf` is a builtin that, given a bound subject, generates every object it maps
+        // to, exercising the `Generator` half of `Builtin` end to end through `solve`.
+        let mut builtins = BuiltinRegistry::new();
+        builtins.register(
+            "siblingOf",
+            Builtin::Generator(Box::new(|who: &&str| match *who {
+                "alice" => vec!["bob", "carol"],
+                _ => vec![],
+            })),
+        );
+        let goal = [exa("alice"), exa("siblingOf"), any(0)];
+
+        let solutions: Vec<_> = solve(goal, &[], &[], &builtins).collect();
+        assert_eq!(solutions.len(), 2);
+    }
+}
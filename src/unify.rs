@@ -0,0 +1,195 @@
+//! General unification of entity triples, used for backward reasoning, rule subsumption checks,
+//! and joining two rule premises. `lower` only ever assigns fresh local names; it never unifies
+//! two patterns against each other, which is what this module adds.
+//!
+//! Triples are flat (subject/property/object, no nested terms), so this is atomic unification: a
+//! `Subst` is a union-find over unbound names, where each class is either unresolved or bound to
+//! a `Bound` value. No occurs check is needed since terms cannot contain variables.
+
+use crate::rule::Entity;
+use alloc::collections::BTreeMap;
+
+/// A substitution environment built up by [`unify`]. Tracks which unbound names have been linked
+/// together and which of those classes have been bound to a concrete value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Subst<Unbound, Bound> {
+    // union-find parent pointers; a name absent from this map is its own representative
+    parent: BTreeMap<Unbound, Unbound>,
+    // representative -> bound value, once known
+    bound: BTreeMap<Unbound, Bound>,
+}
+
+impl<Unbound: Ord + Clone, Bound: Clone + PartialEq> Subst<Unbound, Bound> {
+    pub fn new() -> Self {
+        Self {
+            parent: BTreeMap::new(),
+            bound: BTreeMap::new(),
+        }
+    }
+
+    /// Find the representative of `name`'s class, compressing the path as it goes.
+    fn find(&mut self, name: &Unbound) -> Unbound {
+        match self.parent.get(name).cloned() {
+            Some(parent) if &parent != name => {
+                let root = self.find(&parent);
+                self.parent.insert(name.clone(), root.clone());
+                root
+            }
+            _ => name.clone(),
+        }
+    }
+
+    /// Resolve `name` through the substitution: an `Exactly` if its class is bound, else the
+    /// `Any` naming its (possibly merged) representative.
+    pub fn resolve(&mut self, name: &Unbound) -> Entity<Unbound, Bound> {
+        let root = self.find(name);
+        match self.bound.get(&root).cloned() {
+            Some(value) => Entity::Exactly(value),
+            None => Entity::Any(root),
+        }
+    }
+
+    fn bind(&mut self, name: &Unbound, value: Bound) -> Option<()> {
+        let root = self.find(name);
+        match self.bound.get(&root) {
+            Some(existing) if *existing != value => None,
+            Some(_) => Some(()),
+            None => {
+                self.bound.insert(root, value);
+                Some(())
+            }
+        }
+    }
+
+    fn union(&mut self, a: &Unbound, b: &Unbound) -> Option<()> {
+        let ra = self.find(a);
+        let rb = self.find(b);
+        if ra == rb {
+            return Some(());
+        }
+        match (self.bound.get(&ra).cloned(), self.bound.get(&rb).cloned()) {
+            (Some(va), Some(vb)) if va != vb => return None,
+            _ => {}
+        }
+        if let Some(vb) = self.bound.remove(&rb) {
+            self.bound.entry(ra.clone()).or_insert(vb);
+        }
+        self.parent.insert(rb, ra);
+        Some(())
+    }
+}
+
+/// Unify two entities under `env`: resolve each through `env`, then either two `Exactly` values
+/// unify iff equal, an `Exactly` and an `Any` binds the variable, or two `Any`s link their
+/// classes together.
+pub fn unify_one<Unbound: Ord + Clone, Bound: Clone + PartialEq>(
+    a: &Entity<Unbound, Bound>,
+    b: &Entity<Unbound, Bound>,
+    env: &mut Subst<Unbound, Bound>,
+) -> Option<()> {
+    let a = match a {
+        Entity::Any(name) => env.resolve(name),
+        Entity::Exactly(value) => Entity::Exactly(value.clone()),
+    };
+    let b = match b {
+        Entity::Any(name) => env.resolve(name),
+        Entity::Exactly(value) => Entity::Exactly(value.clone()),
+    };
+    match (a, b) {
+        (Entity::Exactly(va), Entity::Exactly(vb)) => {
+            if va != vb {
+                return None;
+            }
+        }
+        (Entity::Exactly(v), Entity::Any(name)) | (Entity::Any(name), Entity::Exactly(v)) => {
+            env.bind(&name, v)?;
+        }
+        (Entity::Any(na), Entity::Any(nb)) => {
+            env.union(&na, &nb)?;
+        }
+    }
+    Some(())
+}
+
+/// Unify two entity triples under `env`, extending it with whatever bindings and variable
+/// links are needed to make the triples equal, or leaving it untouched and returning `None` if
+/// they cannot be unified. Each position is unified independently via [`unify_one`].
+pub fn unify<Unbound: Ord + Clone, Bound: Clone + PartialEq>(
+    a: &[Entity<Unbound, Bound>; 3],
+    b: &[Entity<Unbound, Bound>; 3],
+    env: &mut Subst<Unbound, Bound>,
+) -> Option<()> {
+    let mut scratch = env.clone();
+    for (x, y) in a.iter().zip(b.iter()) {
+        unify_one(x, y, &mut scratch)?;
+    }
+    *env = scratch;
+    Some(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::{any, exa};
+
+    #[test]
+    fn unify_binds_variable() {
+        let mut env = Subst::<&str, &str>::new();
+        unify(
+            &[any("a"), exa("parent"), any("b")],
+            &[exa("alice"), exa("parent"), exa("bob")],
+            &mut env,
+        )
+        .unwrap();
+        assert_eq!(env.resolve(&"a"), Entity::Exactly("alice"));
+        assert_eq!(env.resolve(&"b"), Entity::Exactly("bob"));
+    }
+
+    #[test]
+    fn unify_links_variables() {
+        let mut env = Subst::<&str, &str>::new();
+        unify(
+            &[any("a"), exa("parent"), any("b")],
+            &[any("x"), exa("parent"), any("y")],
+            &mut env,
+        )
+        .unwrap();
+        // ?a and ?x are now the same class, so binding one resolves the other
+        unify(
+            &[any("x"), exa("parent"), any("y")],
+            &[exa("alice"), exa("parent"), exa("bob")],
+            &mut env,
+        )
+        .unwrap();
+        assert_eq!(env.resolve(&"a"), Entity::Exactly("alice"));
+        assert_eq!(env.resolve(&"b"), Entity::Exactly("bob"));
+    }
+
+    #[test]
+    fn unify_conflicting_bound_values_fails() {
+        let mut env = Subst::<&str, &str>::new();
+        unify(
+            &[exa("alice"), exa("parent"), any("b")],
+            &[any("a"), exa("parent"), any("b")],
+            &mut env,
+        )
+        .unwrap();
+        assert!(unify(
+            &[any("a"), exa("parent"), any("b")],
+            &[exa("bob"), exa("parent"), any("b")],
+            &mut env,
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn unify_mismatched_exactly_fails() {
+        let mut env = Subst::<&str, &str>::new();
+        assert!(unify(
+            &[exa("alice"), exa("parent"), any("b")],
+            &[exa("bob"), exa("parent"), any("b")],
+            &mut env,
+        )
+        .is_none());
+    }
+}